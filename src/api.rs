@@ -0,0 +1,205 @@
+use actix_web::{HttpRequest, HttpResponse, Json};
+use actix_web::dev::FromParam;
+use futures::{Future, Stream};
+use gio::prelude::*;
+use ostree::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+use crate::app::{AppState, Claims};
+use crate::errors::ApiError;
+use crate::jobs::{EnqueueJob, JobKind};
+use crate::request_id;
+
+/// OSTree splits a loose object's checksum into a two-character directory
+/// and the remaining hex digest, e.g. `ab/cdef...`.
+fn object_relpath(checksum: &str, ext: &str) -> Option<PathBuf> {
+    if checksum.len() < 3 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let (dir, rest) = checksum.split_at(2);
+    Some(Path::new("objects").join(dir).join(format!("{}.{}", rest, ext)))
+}
+
+fn build_dir(req: &HttpRequest<AppState>, id: &str) -> actix_web::Result<PathBuf, ApiError> {
+    let safe_id = PathBuf::from_param(id).map_err(|_| ApiError::BadRequest("invalid build id".into()))?;
+    Ok(req.state().config.build_repo_base_path.join(safe_id))
+}
+
+/// Returns, of the checksums listed in `?wanted=a,b,c`, the ones not already
+/// present in the build repo. Lets a client resume an interrupted upload by
+/// only re-sending what's actually missing instead of restarting from zero.
+pub fn missing_objects(req: HttpRequest<AppState>) -> actix_web::Result<Json<Vec<String>>, ApiError> {
+    let id: String = req.match_info().query("id").map_err(|_| ApiError::NotFound)?;
+    let dir = build_dir(&req, &id)?;
+    let wanted = req
+        .query()
+        .get("wanted")
+        .cloned()
+        .unwrap_or_default();
+
+    let missing = wanted
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .filter(|checksum| {
+            let relpath = match object_relpath(checksum, "filez") {
+                Some(p) => p,
+                None => return false,
+            };
+            !dir.join(relpath).exists()
+        })
+        .map(|s| s.to_string())
+        .collect();
+
+    Ok(Json(missing))
+}
+
+// `ostree::Repo::write_content` hashes the same GVariant-wrapped,
+// uncompressed form the rest of the OSTree toolchain does, so use it
+// instead of re-deriving the checksum format here.
+fn verify_content_checksum(
+    repo_path: &Path,
+    tmp_path: &Path,
+    checksum: &str,
+    length: u64,
+) -> Result<(), ApiError> {
+    let repo = ostree::Repo::new_for_path(repo_path);
+    repo.open(gio::NONE_CANCELLABLE)
+        .map_err(|_| ApiError::InternalServerError)?;
+
+    let file = gio::File::new_for_path(tmp_path);
+    let stream = file
+        .read(gio::NONE_CANCELLABLE)
+        .map_err(|_| ApiError::InternalServerError)?;
+
+    repo.write_content(Some(checksum), &stream, length, gio::NONE_CANCELLABLE)
+        .map(|_| ())
+        .map_err(|e| {
+            // write_content also fails on disk I/O errors, a corrupt repo,
+            // etc.; only report 409 when libostree actually flagged a
+            // checksum mismatch, not on every failure.
+            if e.message().to_lowercase().contains("checksum") {
+                ApiError::ChecksumMismatch
+            } else {
+                ApiError::InternalServerError
+            }
+        })
+}
+
+/// PUT /build/{id}/object/{checksum}: streams the body to a temp file so a
+/// large object can't blow up RSS, then has libostree verify it against
+/// `{checksum}` and store it in the build repo; the temp file itself is
+/// discarded. Returns 409 on a checksum mismatch so a client can retry
+/// just that object.
+pub fn upload_object(
+    req: HttpRequest<AppState>,
+) -> Box<dyn Future<Item = HttpResponse, Error = ApiError>> {
+    let id: String = match req.match_info().query("id") {
+        Ok(id) => id,
+        Err(_) => return Box::new(futures::future::err(ApiError::NotFound)),
+    };
+    let checksum: String = match req.match_info().query("checksum") {
+        Ok(c) => c,
+        Err(_) => return Box::new(futures::future::err(ApiError::NotFound)),
+    };
+    let dir = match build_dir(&req, &id) {
+        Ok(dir) => dir,
+        Err(e) => return Box::new(futures::future::err(e)),
+    };
+    let relpath = match object_relpath(&checksum, "filez") {
+        Some(p) => p,
+        None => return Box::new(futures::future::err(ApiError::BadRequest("invalid checksum".into()))),
+    };
+    let dest = dir.join(&relpath);
+    if let Some(parent) = dest.parent() {
+        if fs::create_dir_all(parent).is_err() {
+            return Box::new(futures::future::err(ApiError::InternalServerError));
+        }
+    }
+    let tmp_file = match NamedTempFile::new_in(dest.parent().unwrap()) {
+        Ok(f) => f,
+        Err(_) => return Box::new(futures::future::err(ApiError::InternalServerError)),
+    };
+    let repo_path = dir.clone();
+
+    Box::new(
+        req.payload()
+            .from_err()
+            .fold((tmp_file, 0u64), |(mut tmp_file, written), chunk| {
+                tmp_file
+                    .write_all(&chunk)
+                    .map_err(|_| ApiError::InternalServerError)?;
+                Ok((tmp_file, written + chunk.len() as u64))
+            })
+            .and_then(move |(tmp_file, written)| {
+                // write_content already stored the verified object at its
+                // canonical path inside repo_path (== dest); persisting
+                // tmp_file on top of it would overwrite that with the raw,
+                // uncompressed upload.
+                verify_content_checksum(&repo_path, tmp_file.path(), &checksum, written)?;
+                Ok(HttpResponse::Ok().finish())
+            }),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct CommitRequest {
+    #[serde(default)]
+    pub refs: Vec<String>,
+}
+
+/// POST /build/{id}/commit: enqueues a `Commit` job for the refs in the
+/// JSON body. The request id is forwarded to `EnqueueJob` so the webhook
+/// fired once the job settles can be tied back to this API call.
+pub fn commit(
+    req: HttpRequest<AppState>,
+) -> Box<dyn Future<Item = HttpResponse, Error = ApiError>> {
+    let id: String = match req.match_info().query("id") {
+        Ok(id) => id,
+        Err(_) => return Box::new(futures::future::err(ApiError::NotFound)),
+    };
+    let build_id: i32 = match id.parse() {
+        Ok(n) => n,
+        Err(_) => return Box::new(futures::future::err(ApiError::BadRequest("invalid build id".into()))),
+    };
+    let request_id = request_id::request_id(&req);
+    let span = request_id::span(&req);
+    let (prefix, scope) = match req.extensions().get::<Claims>() {
+        Some(claims) => (claims.prefix.clone().unwrap_or_default(), claims.scope.clone()),
+        None => (Vec::new(), Vec::new()),
+    };
+    let job_queue = req.state().job_queue.clone();
+
+    Box::new(req.json().from_err().and_then(move |body: CommitRequest| {
+        let _entered = span.as_ref().map(|span| span.enter());
+        info!("enqueuing commit job");
+        let contents = json!({ "refs": body.refs, "prefix": prefix, "scope": scope }).to_string();
+        job_queue
+            .send(EnqueueJob { kind: JobKind::Commit, build_id, contents, request_id })
+            .from_err()
+            .and_then(|result| {
+                let job_id = result?;
+                Ok(HttpResponse::Ok().json(json!({ "job_id": job_id })))
+            })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_relpath_splits_checksum_into_ostree_layout() {
+        let checksum = "ab".to_string() + &"c".repeat(62);
+        let path = object_relpath(&checksum, "filez").unwrap();
+        assert_eq!(path, Path::new("objects").join("ab").join(format!("{}.filez", "c".repeat(62))));
+    }
+
+    #[test]
+    fn object_relpath_rejects_non_hex_and_short_checksums() {
+        assert!(object_relpath("ab", "filez").is_none());
+        assert!(object_relpath(&"zz".repeat(32), "filez").is_none());
+    }
+}
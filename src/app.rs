@@ -1,5 +1,5 @@
 use crate::actix::prelude::*;
-use actix_web::{self, fs, middleware};
+use actix_web::{self, fs};
 use actix_web::{App, http::Method, HttpRequest, fs::NamedFile};
 use crate::models::DbExecutor;
 use std::path::PathBuf;
@@ -9,6 +9,8 @@ use std::sync::Arc;
 use crate::api;
 use crate::tokens::{TokenParser};
 use crate::jobs::{JobQueue};
+use crate::notifier::NotifierConfig;
+use crate::request_id::RequestId;
 use actix_web::dev::FromParam;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -31,6 +33,8 @@ pub struct Config {
     pub main_gpg_key: Option<String>,
     pub main_gpg_key_content: Option<String>,
     pub secret: Vec<u8>,
+    pub notifier: NotifierConfig,
+    pub hooks_dir: Option<PathBuf>,
 }
 
 #[derive(Clone)]
@@ -70,7 +74,7 @@ pub fn create_app(
         .expect("failed constructing repo handler");
 
     App::with_state(state)
-        .middleware(middleware::Logger::default())
+        .middleware(RequestId)
         .scope("/api/v1", |scope| {
             scope
                 .middleware(TokenParser::new(&config.secret))
@@ -82,7 +86,7 @@ pub fn create_app(
                 .resource("/build/{id}/build_ref", |r| r.method(Method::POST).with(api::create_build_ref))
                 .resource("/build/{id}/build_ref/{ref_id}", |r| { r.name("show_build_ref"); r.method(Method::GET).with(api::get_build_ref) })
                 .resource("/build/{id}/missing_objects", |r| r.method(Method::GET).with(api::missing_objects))
-                .resource("/build/{id}/upload", |r| r.method(Method::POST).with(api::upload))
+                .resource("/build/{id}/object/{checksum}", |r| r.method(Method::PUT).with(api::upload_object))
                 .resource("/build/{id}/commit", |r| { r.name("show_commit_job");
                                                       r.method(Method::POST).with(api::commit);
                                                       r.method(Method::GET).with(api::get_commit_job) })
@@ -0,0 +1,203 @@
+extern crate argparse;
+extern crate chrono;
+#[macro_use] extern crate diesel;
+extern crate dotenv;
+
+use argparse::{ArgumentParser, Store, StoreOption, StoreTrue};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use dotenv::dotenv;
+use std::env;
+use std::fs;
+use std::process;
+
+// src/bin/*.rs are each their own crate (same as gentoken.rs), so they
+// can't `use crate::models` from the main `repo-manager` binary. `schema.rs`
+// only needs diesel, so it's shared via `include!`; `models.rs` also pulls
+// in actix (for `DbExecutor`) and serde_derive, neither of which this CLI
+// needs, so its query structs are redefined locally instead.
+mod schema {
+    include!("../schema.rs");
+}
+
+use schema::builds::dsl as builds_dsl;
+use schema::jobs::dsl as jobs_dsl;
+
+#[derive(Queryable, Debug, Clone)]
+struct Build {
+    id: i32,
+    created: chrono::NaiveDateTime,
+    repo_state: i16,
+    build_repo_base_path: String,
+}
+
+#[derive(Queryable, Debug, Clone)]
+struct BuildRef {
+    id: i32,
+    build_id: i32,
+    ref_name: String,
+    commit: String,
+}
+
+#[derive(Queryable, Debug, Clone)]
+struct Job {
+    id: i32,
+    kind: i16,
+    status: i16,
+    build_id: Option<i32>,
+    contents: String,
+    results: String,
+    log: String,
+    request_id: Option<String>,
+}
+
+fn connect() -> PgConnection {
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    PgConnection::establish(&database_url)
+        .unwrap_or_else(|e| panic!("Failed to connect to {}: {}", database_url, e))
+}
+
+fn cmd_list_builds(args: &[String]) {
+    let mut state: Option<String> = None;
+    let mut older_than_days: Option<i64> = None;
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("List builds, optionally filtered by state/age.");
+        ap.refer(&mut state)
+            .add_option(&["--state"], StoreOption, "Filter by repo_state");
+        ap.refer(&mut older_than_days)
+            .add_option(&["--older-than"], StoreOption, "Only builds older than N days");
+        ap.parse(args.to_vec(), &mut std::io::stdout(), &mut std::io::stderr())
+            .unwrap_or_else(|code| process::exit(code));
+    }
+
+    let conn = connect();
+    let mut query = builds_dsl::builds.into_boxed();
+    if let Some(state) = &state {
+        let state: i16 = state.parse().expect("--state must be numeric");
+        query = query.filter(builds_dsl::repo_state.eq(state));
+    }
+    if let Some(days) = older_than_days {
+        let cutoff = (Utc::now() - Duration::days(days)).naive_utc();
+        query = query.filter(builds_dsl::created.lt(cutoff));
+    }
+
+    let results: Vec<Build> = query.load(&conn).expect("Failed to list builds");
+    for build in results {
+        println!("{}\t{}\t{}\t{}", build.id, build.created, build.repo_state, build.build_repo_base_path);
+    }
+}
+
+fn cmd_show_build(args: &[String]) {
+    let mut id: i32 = 0;
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Show a build's refs and jobs.");
+        ap.refer(&mut id).required().add_argument("id", Store, "Build id");
+        ap.parse(args.to_vec(), &mut std::io::stdout(), &mut std::io::stderr())
+            .unwrap_or_else(|code| process::exit(code));
+    }
+
+    let conn = connect();
+    let build: Build = builds_dsl::builds
+        .find(id)
+        .first(&conn)
+        .unwrap_or_else(|_| panic!("No such build {}", id));
+    println!("build {}: state={} path={}", build.id, build.repo_state, build.build_repo_base_path);
+
+    let build_refs: Vec<BuildRef> = schema::build_refs::dsl::build_refs
+        .filter(schema::build_refs::dsl::build_id.eq(id))
+        .load(&conn)
+        .expect("Failed to load build refs");
+    for build_ref in build_refs {
+        println!("  ref {} -> {}", build_ref.ref_name, build_ref.commit);
+    }
+
+    let jobs: Vec<Job> = jobs_dsl::jobs
+        .filter(jobs_dsl::build_id.eq(id))
+        .load(&conn)
+        .expect("Failed to load jobs");
+    for job in jobs {
+        println!("  job {}: kind={} status={}", job.id, job.kind, job.status);
+    }
+}
+
+fn cmd_purge(args: &[String]) {
+    let mut older_than_days: i64 = 30;
+    let mut state: Option<i16> = None;
+    let mut dry_run = false;
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Bulk-clean abandoned builds and their on-disk repos.");
+        ap.refer(&mut older_than_days)
+            .add_option(&["--older-than"], Store, "Age in days (default 30)");
+        ap.refer(&mut state)
+            .add_option(&["--state"], StoreOption, "repo_state to purge (required; there's no enum for this column, check it against what actually got written)");
+        ap.refer(&mut dry_run)
+            .add_option(&["--dry-run"], StoreTrue, "Only print what would be purged");
+        ap.parse(args.to_vec(), &mut std::io::stdout(), &mut std::io::stderr())
+            .unwrap_or_else(|code| process::exit(code));
+    }
+    let state = state.unwrap_or_else(|| {
+        eprintln!("--state is required: repo_state has no defined enum, so purge refuses to guess which state means \"failed\"");
+        process::exit(1);
+    });
+
+    let conn = connect();
+    let cutoff = (Utc::now() - Duration::days(older_than_days)).naive_utc();
+    let abandoned: Vec<Build> = builds_dsl::builds
+        .filter(builds_dsl::repo_state.eq(state))
+        .filter(builds_dsl::created.lt(cutoff))
+        .load(&conn)
+        .expect("Failed to list abandoned builds");
+
+    for build in &abandoned {
+        println!("purging build {} ({})", build.id, build.build_repo_base_path);
+        if !dry_run {
+            let _ = fs::remove_dir_all(&build.build_repo_base_path);
+            diesel::delete(builds_dsl::builds.find(build.id))
+                .execute(&conn)
+                .unwrap_or_else(|e| panic!("Failed to delete build {}: {}", build.id, e));
+        }
+    }
+    println!("{} build(s) purged", abandoned.len());
+}
+
+fn cmd_retry_job(args: &[String]) {
+    let mut id: i32 = 0;
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Requeue a stuck job.");
+        ap.refer(&mut id).required().add_argument("id", Store, "Job id");
+        ap.parse(args.to_vec(), &mut std::io::stdout(), &mut std::io::stderr())
+            .unwrap_or_else(|code| process::exit(code));
+    }
+
+    let conn = connect();
+    diesel::update(jobs_dsl::jobs.find(id))
+        .set(jobs_dsl::status.eq(0)) // JobStatus::New
+        .execute(&conn)
+        .unwrap_or_else(|e| panic!("Failed to requeue job {}: {}", id, e));
+    println!("job {} requeued", id);
+}
+
+fn main() {
+    dotenv().ok();
+    let mut args: Vec<String> = env::args().collect();
+    args.remove(0);
+    if args.is_empty() {
+        eprintln!("Usage: repo-manager-ctl <list-builds|show-build|purge|retry-job> [args...]");
+        process::exit(1);
+    }
+    let subcommand = args.remove(0);
+    match subcommand.as_str() {
+        "list-builds" => cmd_list_builds(&args),
+        "show-build" => cmd_show_build(&args),
+        "purge" => cmd_purge(&args),
+        "retry-job" => cmd_retry_job(&args),
+        other => {
+            eprintln!("Unknown subcommand: {}", other);
+            process::exit(1)
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use actix_web::{HttpResponse, http::StatusCode};
+
+#[derive(Debug, Fail)]
+pub enum ApiError {
+    #[fail(display = "Internal Server Error")]
+    InternalServerError,
+    #[fail(display = "Not found")]
+    NotFound,
+    #[fail(display = "Bad request: {}", _0)]
+    BadRequest(String),
+    #[fail(display = "Checksum mismatch")]
+    ChecksumMismatch,
+}
+
+impl actix_web::ResponseError for ApiError {
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            ApiError::InternalServerError => HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR),
+            ApiError::NotFound => HttpResponse::new(StatusCode::NOT_FOUND),
+            ApiError::BadRequest(msg) => HttpResponse::BadRequest().body(msg.clone()),
+            ApiError::ChecksumMismatch => HttpResponse::new(StatusCode::CONFLICT),
+        }
+    }
+}
+
+impl From<actix_web::error::PayloadError> for ApiError {
+    fn from(_: actix_web::error::PayloadError) -> Self {
+        ApiError::InternalServerError
+    }
+}
+
+impl From<actix_web::error::JsonPayloadError> for ApiError {
+    fn from(_: actix_web::error::JsonPayloadError) -> Self {
+        ApiError::BadRequest("invalid JSON body".into())
+    }
+}
+
+impl From<actix::MailboxError> for ApiError {
+    fn from(_: actix::MailboxError) -> Self {
+        ApiError::InternalServerError
+    }
+}
+
+impl From<diesel::result::Error> for ApiError {
+    fn from(_: diesel::result::Error) -> Self {
+        ApiError::InternalServerError
+    }
+}
@@ -0,0 +1,120 @@
+use rlua::{Lua, Table};
+use std::fs;
+use std::path::PathBuf;
+
+/// Loads `<hooks_dir>/hooks.lua`, if configured, and exposes its
+/// `validate_commit`/`validate_publish` callbacks to the commit and
+/// publish jobs. A non-nil error string return aborts the job with that
+/// message; a script that doesn't define a callback is treated as "allow".
+pub struct Hooks {
+    lua: Lua,
+}
+
+#[derive(Debug, Fail)]
+pub enum HookError {
+    #[fail(display = "Failed to read hook script {}: {}", _0, _1)]
+    Io(String, String),
+    #[fail(display = "Failed to load hook script {}: {}", _0, _1)]
+    Load(String, String),
+    #[fail(display = "Hook error: {}", _0)]
+    Lua(String),
+    #[fail(display = "{}", _0)]
+    Rejected(String),
+}
+
+impl Hooks {
+    pub fn load(hooks_dir: &PathBuf) -> Result<Self, HookError> {
+        let script_path = hooks_dir.join("hooks.lua");
+        let source = fs::read_to_string(&script_path)
+            .map_err(|e| HookError::Io(script_path.display().to_string(), e.to_string()))?;
+        let lua = Lua::new();
+        lua.context(|ctx| ctx.load(&source).exec())
+            .map_err(|e| HookError::Load(script_path.display().to_string(), e.to_string()))?;
+        Ok(Hooks { lua })
+    }
+
+    /// Runs `validate_commit(build_id, refs, metadata)`. `metadata.prefix`/
+    /// `metadata.scope` are the authorizing token's allowed branch prefixes
+    /// and scopes (not per-ref); `metadata.size` is the build directory's
+    /// total on-disk size in bytes, so a script can allow/reject per-build.
+    pub fn validate_commit(
+        &self,
+        build_id: i32,
+        refs: &[String],
+        prefix: &[String],
+        scope: &[String],
+        size: u64,
+    ) -> Result<(), HookError> {
+        self.call_validator("validate_commit", build_id, refs, prefix, scope, size)
+    }
+
+    /// Runs `validate_publish(build_id, refs)` with the same calling
+    /// convention as `validate_commit`.
+    pub fn validate_publish(
+        &self,
+        build_id: i32,
+        refs: &[String],
+        prefix: &[String],
+        scope: &[String],
+        size: u64,
+    ) -> Result<(), HookError> {
+        self.call_validator("validate_publish", build_id, refs, prefix, scope, size)
+    }
+
+    fn call_validator(
+        &self,
+        name: &str,
+        build_id: i32,
+        refs: &[String],
+        prefix: &[String],
+        scope: &[String],
+        size: u64,
+    ) -> Result<(), HookError> {
+        self.lua.context(|ctx| {
+            let globals = ctx.globals();
+            let func: rlua::Function = match globals.get(name) {
+                Ok(f) => f,
+                Err(_) => return Ok(()),
+            };
+
+            let lua_refs = ctx.create_sequence_from(refs.iter().cloned())
+                .map_err(|e| HookError::Lua(e.to_string()))?;
+            let metadata: Table = ctx.create_table().map_err(|e| HookError::Lua(e.to_string()))?;
+            metadata
+                .set(
+                    "prefix",
+                    ctx.create_sequence_from(prefix.iter().cloned())
+                        .map_err(|e| HookError::Lua(e.to_string()))?,
+                )
+                .map_err(|e| HookError::Lua(e.to_string()))?;
+            metadata
+                .set(
+                    "scope",
+                    ctx.create_sequence_from(scope.iter().cloned())
+                        .map_err(|e| HookError::Lua(e.to_string()))?,
+                )
+                .map_err(|e| HookError::Lua(e.to_string()))?;
+            metadata
+                .set("size", size)
+                .map_err(|e| HookError::Lua(e.to_string()))?;
+
+            let result: rlua::Value = func
+                .call((build_id, lua_refs, metadata))
+                .map_err(|e| HookError::Lua(e.to_string()))?;
+
+            match result {
+                rlua::Value::Nil => Ok(()),
+                rlua::Value::String(msg) => Err(HookError::Rejected(
+                    msg.to_str().unwrap_or("rejected by hook").to_string(),
+                )),
+                // Anything else (a number, a table, a boolean...) isn't a
+                // documented return value; fail closed rather than let a
+                // buggy script silently wave a build through.
+                other => Err(HookError::Rejected(format!(
+                    "{} returned an unexpected value ({:?}); rejecting",
+                    name, other
+                ))),
+            }
+        })
+    }
+}
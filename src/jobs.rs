@@ -0,0 +1,286 @@
+use actix::prelude::*;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app::Config;
+use crate::hooks::{HookError, Hooks};
+use crate::models::{Job, NewJob};
+use crate::notifier::{self, JobEvent, NotifierConfig};
+use crate::schema::jobs::dsl::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(i16)]
+pub enum JobKind {
+    Commit = 0,
+    Publish = 1,
+    Purge = 2,
+    UpdateRepo = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(i16)]
+pub enum JobStatus {
+    New = 0,
+    Started = 1,
+    Completed = 2,
+    Failed = 3,
+}
+
+pub struct JobQueue {
+    config: Arc<Config>,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    notifier_config: Arc<NotifierConfig>,
+    stopping: bool,
+}
+
+impl Actor for JobQueue {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.notify(ProcessJobs);
+    }
+}
+
+struct ProcessJobs;
+
+impl Message for ProcessJobs {
+    type Result = ();
+}
+
+impl Handler<ProcessJobs> for JobQueue {
+    type Result = ();
+
+    fn handle(&mut self, _msg: ProcessJobs, ctx: &mut Self::Context) {
+        if self.stopping {
+            return;
+        }
+        if let Err(e) = self.process_pending() {
+            error!("Error processing job queue: {}", e);
+        }
+        ctx.run_later(Duration::from_secs(1), |_act, ctx| {
+            ctx.notify(ProcessJobs);
+        });
+    }
+}
+
+pub struct StopJobQueue();
+
+impl Message for StopJobQueue {
+    type Result = ();
+}
+
+impl Handler<StopJobQueue> for JobQueue {
+    type Result = ();
+
+    fn handle(&mut self, _msg: StopJobQueue, _ctx: &mut Self::Context) {
+        self.stopping = true;
+    }
+}
+
+/// Inserts a New job for `build_id`, to be picked up by the queue's next
+/// tick. `contents` is job-kind-specific metadata (refs, commit, publish
+/// prefix/scope) serialized as JSON.
+pub struct EnqueueJob {
+    pub kind: JobKind,
+    pub build_id: i32,
+    pub contents: String,
+    pub request_id: Option<String>,
+}
+
+impl Message for EnqueueJob {
+    type Result = Result<i32, diesel::result::Error>;
+}
+
+impl Handler<EnqueueJob> for JobQueue {
+    type Result = Result<i32, diesel::result::Error>;
+
+    fn handle(&mut self, msg: EnqueueJob, _ctx: &mut Self::Context) -> Self::Result {
+        let conn = self.pool.get().expect("Failed to get db connection");
+        let new_job = NewJob {
+            kind: msg.kind as i16,
+            status: JobStatus::New as i16,
+            build_id: Some(msg.build_id),
+            contents: msg.contents,
+            results: String::new(),
+            log: String::new(),
+            request_id: msg.request_id,
+        };
+        let inserted: Job = diesel::insert_into(jobs).values(&new_job).get_result(&conn)?;
+        Ok(inserted.id)
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct JobContents {
+    #[serde(default)]
+    refs: Vec<String>,
+    #[serde(default)]
+    prefix: Vec<String>,
+    #[serde(default)]
+    scope: Vec<String>,
+    #[serde(default)]
+    commit: Option<String>,
+}
+
+/// Total on-disk size of a build's repo directory, in bytes. Best-effort:
+/// a missing directory (no objects uploaded yet) or a race with an
+/// in-flight upload just reads as a smaller size rather than failing the job.
+fn build_dir_size(dir: &Path) -> u64 {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => build_dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+impl JobQueue {
+    /// Picks up the oldest New job, runs the policy hook and the job's
+    /// kind-specific handler, and moves it to Completed/Failed, firing the
+    /// webhook notifier either way.
+    fn process_pending(&mut self) -> Result<(), diesel::result::Error> {
+        let conn = self.pool.get().expect("Failed to get db connection");
+
+        let job: Job = match jobs
+            .filter(status.eq(JobStatus::New as i16))
+            .order(id.asc())
+            .first(&conn)
+            .optional()?
+        {
+            Some(job) => job,
+            None => return Ok(()),
+        };
+
+        diesel::update(jobs.find(job.id))
+            .set(status.eq(JobStatus::Started as i16))
+            .execute(&conn)?;
+
+        let job_kind = match job.kind {
+            0 => JobKind::Commit,
+            1 => JobKind::Publish,
+            2 => JobKind::Purge,
+            _ => JobKind::UpdateRepo,
+        };
+        let build_id = job.build_id.unwrap_or(0);
+        let contents: JobContents = serde_json::from_str(&job.contents).unwrap_or_default();
+
+        let span = info_span!("job", build_id, job_id = job.id, kind = ?job_kind);
+        let _entered = span.enter();
+
+        let outcome = self
+            .run_hook(job_kind, build_id, &contents.refs, &contents.prefix, &contents.scope)
+            .map_err(|e| e.to_string())
+            .and_then(|()| self.run_job(job_kind, build_id, &contents));
+
+        let (new_status, log_message) = match outcome {
+            Ok(()) => (JobStatus::Completed, String::new()),
+            Err(message) => (JobStatus::Failed, message),
+        };
+
+        diesel::update(jobs.find(job.id))
+            .set((status.eq(new_status as i16), log.eq(&log_message)))
+            .execute(&conn)?;
+
+        self.notify_job_state(
+            build_id,
+            job.id,
+            job_kind,
+            new_status,
+            contents.refs.get(0).cloned(),
+            contents.commit,
+            job.request_id,
+        );
+
+        Ok(())
+    }
+
+    /// Performs the OSTree operation for `kind`. None of these are
+    /// implemented yet, so fail the job rather than report success for work
+    /// that never happened; wire in the real commit/publish/purge/
+    /// update-repo logic here once it exists.
+    fn run_job(&self, kind: JobKind, _build_id: i32, _contents: &JobContents) -> Result<(), String> {
+        match kind {
+            JobKind::Commit | JobKind::Publish | JobKind::Purge | JobKind::UpdateRepo => {
+                Err(format!("{:?} job handling is not implemented yet", kind))
+            }
+        }
+    }
+
+    /// Loads `hooks.lua` from `HOOKS_DIR`, if configured, and runs
+    /// `validate_commit`/`validate_publish` before the commit/publish job
+    /// performs its OSTree operation. A script-rejected job is failed with
+    /// the script's error message rather than proceeding.
+    fn run_hook(
+        &self,
+        kind: JobKind,
+        build_id: i32,
+        refs: &[String],
+        prefix: &[String],
+        scope: &[String],
+    ) -> Result<(), HookError> {
+        let hooks_dir = match &self.config.hooks_dir {
+            Some(dir) => dir,
+            None => return Ok(()),
+        };
+        let hooks = Hooks::load(hooks_dir)?;
+        let size = build_dir_size(&self.config.build_repo_base_path.join(build_id.to_string()));
+        match kind {
+            JobKind::Commit => hooks.validate_commit(build_id, refs, prefix, scope, size),
+            JobKind::Publish => hooks.validate_publish(build_id, refs, prefix, scope, size),
+            _ => Ok(()),
+        }
+    }
+
+    /// Called once a job has settled into Completed or Failed. Fires the
+    /// webhook notifier out-of-band so a slow or broken endpoint never
+    /// blocks the next iteration of the queue. `request_id` (if the job was
+    /// triggered synchronously from an API call) is forwarded to the
+    /// webhook payload so a client can tie the two together.
+    fn notify_job_state(
+        &self,
+        build_id: i32,
+        job_id: i32,
+        kind: JobKind,
+        state: JobStatus,
+        ref_name: Option<String>,
+        commit: Option<String>,
+        request_id: Option<String>,
+    ) {
+        let event = JobEvent::new(build_id, job_id, kind, state, ref_name, commit, request_id);
+        notifier::notify(self.notifier_config.clone(), self.config.secret.clone(), event);
+    }
+}
+
+pub fn start_job_executor(
+    config: Arc<Config>,
+    pool: Pool<ConnectionManager<PgConnection>>,
+) -> Addr<JobQueue> {
+    let notifier_config = Arc::new(config.notifier.clone());
+    JobQueue {
+        config,
+        pool,
+        notifier_config,
+        stopping: false,
+    }
+    .start()
+}
+
+pub fn cleanup_started_jobs(
+    pool: &Pool<ConnectionManager<PgConnection>>,
+) -> Result<(), diesel::result::Error> {
+    let conn = pool.get().expect("Failed to get db connection");
+    diesel::update(jobs.filter(status.eq(JobStatus::Started as i16)))
+        .set(status.eq(JobStatus::New as i16))
+        .execute(&conn)?;
+    Ok(())
+}
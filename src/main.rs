@@ -8,17 +8,24 @@ extern crate chrono;
 #[macro_use] extern crate diesel;
 #[macro_use] extern crate diesel_migrations;
 extern crate dotenv;
-extern crate env_logger;
 #[macro_use] extern crate failure;
 extern crate futures;
+extern crate gio;
+extern crate hmac;
+extern crate ostree;
 extern crate r2d2;
+extern crate reqwest;
+extern crate rlua;
+extern crate rustls;
+extern crate sha2;
+#[macro_use] extern crate tracing;
+extern crate tracing_subscriber;
+extern crate uuid;
 extern crate serde;
 #[macro_use] extern crate serde_json;
 #[macro_use] extern crate serde_derive;
 extern crate tempfile;
 extern crate jsonwebtoken as jwt;
-#[macro_use]
-extern crate log;
 extern crate libc;
 
 use actix::prelude::*;
@@ -27,8 +34,12 @@ use actix_web::{server, server::StopServer};
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, ManageConnection};
 use dotenv::dotenv;
+use rustls::internal::pemfile::{certs, pkcs8_private_keys};
+use rustls::{NoClientAuth, ServerConfig};
 use std::env;
+use std::fs::File;
 use std::io;
+use std::io::BufReader;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
@@ -38,7 +49,10 @@ mod api;
 mod app;
 mod db;
 mod errors;
+mod hooks;
 mod models;
+mod notifier;
+mod request_id;
 mod schema;
 mod tokens;
 mod jobs;
@@ -122,9 +136,66 @@ fn load_gpg_key (maybe_gpg_key: &Option<String>, maybe_gpg_homedir: &Option<Stri
 
 embed_migrations!();
 
+/// Installs the global `tracing` subscriber, honoring `RUST_LOG` for
+/// per-target filtering. `LOG_FORMAT=json` switches to newline-delimited
+/// JSON output for ingestion into log aggregators; anything else keeps
+/// the default human-readable format.
+fn init_tracing() {
+    let filter = tracing_subscriber::EnvFilter::from_default_env();
+    let json = env::var("LOG_FORMAT").map(|v| v == "json").unwrap_or(false);
+    if json {
+        tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .json()
+            .init();
+    } else {
+        tracing_subscriber::fmt().with_env_filter(filter).init();
+    }
+}
+
+/// Loads a rustls `ServerConfig` from a PEM cert chain and a PKCS8 key, if
+/// both `TLS_CERT_PATH` and `TLS_KEY_PATH` are set. Returns `None` when
+/// neither is set, so the caller can fall back to plain HTTP; a partial
+/// pair, an unreadable file, or a malformed cert/key is a hard error since
+/// that almost always means a misconfigured deploy.
+fn load_tls_config() -> Option<ServerConfig> {
+    let cert_path = env::var("TLS_CERT_PATH").ok();
+    let key_path = env::var("TLS_KEY_PATH").ok();
+
+    let (cert_path, key_path) = match (cert_path, key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (None, None) => return None,
+        _ => panic!("TLS_CERT_PATH and TLS_KEY_PATH must both be set to enable TLS"),
+    };
+
+    let cert_file = File::open(&cert_path)
+        .unwrap_or_else(|e| panic!("Failed to open TLS_CERT_PATH {}: {}", cert_path, e));
+    let cert_chain = certs(&mut BufReader::new(cert_file))
+        .unwrap_or_else(|_| panic!("Failed to parse TLS cert chain in {}", cert_path));
+    if cert_chain.is_empty() {
+        panic!("No certificates found in {}", cert_path);
+    }
+
+    let key_file = File::open(&key_path)
+        .unwrap_or_else(|e| panic!("Failed to open TLS_KEY_PATH {}: {}", key_path, e));
+    let mut keys = pkcs8_private_keys(&mut BufReader::new(key_file))
+        .unwrap_or_else(|_| panic!("Failed to parse PKCS8 private key in {}", key_path));
+    if keys.is_empty() {
+        panic!("No PKCS8 private keys found in {}", key_path);
+    }
+
+    let mut tls_config = ServerConfig::new(NoClientAuth::new());
+    tls_config
+        .set_single_cert(cert_chain, keys.remove(0))
+        .unwrap_or_else(|e| panic!("TLS cert/key mismatch ({} / {}): {}", cert_path, key_path, e));
+    Some(tls_config)
+}
+
 fn main() {
-    ::std::env::set_var("RUST_LOG", "info");
-    env_logger::init();
+    if env::var_os("RUST_LOG").is_none() {
+        env::set_var("RUST_LOG", "info");
+    }
+    init_tracing();
     let sys = actix::System::new("repo-manage");
 
     dotenv().ok();
@@ -156,6 +227,8 @@ fn main() {
         build_gpg_key: build_gpg_key,
         main_gpg_key: main_gpg_key,
         secret: secret.clone(),
+        notifier: notifier::NotifierConfig::from_env(),
+        hooks_dir: env::var_os("HOOKS_DIR").map(PathBuf::from),
     });
 
 
@@ -183,9 +256,15 @@ fn main() {
     let http_server = server::new(move || {
         app::create_app(db_addr.clone(), &config, jobs_addr_copy.clone())
     });
+    let http_server = match load_tls_config() {
+        Some(tls_config) => http_server
+            .bind_rustls(&bind_to, tls_config)
+            .unwrap_or_else(|e| panic!("Failed to bind TLS to {}: {}", bind_to, e)),
+        None => http_server
+            .bind(&bind_to)
+            .unwrap_or_else(|e| panic!("Failed to bind to {}: {}", bind_to, e)),
+    };
     let server_addr = http_server
-        .bind(&bind_to)
-        .unwrap()
         .disable_signals()
         .start();
 
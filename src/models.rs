@@ -0,0 +1,52 @@
+use actix::prelude::*;
+use chrono::NaiveDateTime;
+use diesel::pg::PgConnection;
+use diesel::r2d2::{ConnectionManager, Pool};
+
+use crate::schema::jobs;
+
+pub struct DbExecutor(pub Pool<ConnectionManager<PgConnection>>);
+
+impl Actor for DbExecutor {
+    type Context = SyncContext<Self>;
+}
+
+#[derive(Queryable, Debug, Clone, Serialize)]
+pub struct Build {
+    pub id: i32,
+    pub created: NaiveDateTime,
+    pub repo_state: i16,
+    pub build_repo_base_path: String,
+}
+
+#[derive(Queryable, Debug, Clone, Serialize)]
+pub struct BuildRef {
+    pub id: i32,
+    pub build_id: i32,
+    pub ref_name: String,
+    pub commit: String,
+}
+
+#[derive(Queryable, Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: i32,
+    pub kind: i16,
+    pub status: i16,
+    pub build_id: Option<i32>,
+    pub contents: String,
+    pub results: String,
+    pub log: String,
+    pub request_id: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "jobs"]
+pub struct NewJob {
+    pub kind: i16,
+    pub status: i16,
+    pub build_id: Option<i32>,
+    pub contents: String,
+    pub results: String,
+    pub log: String,
+    pub request_id: Option<String>,
+}
@@ -0,0 +1,156 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::jobs::{JobKind, JobStatus};
+
+const MAX_RETRIES: u32 = 5;
+const INITIAL_BACKOFF_MS: u64 = 500;
+
+#[derive(Clone, Debug, Default)]
+pub struct NotifierConfig {
+    pub webhook_urls: Vec<String>,
+}
+
+impl NotifierConfig {
+    pub fn from_env() -> Self {
+        let webhook_urls = std::env::var("WEBHOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        NotifierConfig { webhook_urls }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.webhook_urls.is_empty()
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct JobEvent {
+    pub build_id: i32,
+    pub job_id: i32,
+    pub kind: JobKind,
+    pub state: JobStatus,
+    #[serde(rename = "ref")]
+    pub ref_name: Option<String>,
+    pub commit: Option<String>,
+    pub timestamp: i64,
+    pub request_id: Option<String>,
+}
+
+impl JobEvent {
+    pub fn new(
+        build_id: i32,
+        job_id: i32,
+        kind: JobKind,
+        state: JobStatus,
+        ref_name: Option<String>,
+        commit: Option<String>,
+        request_id: Option<String>,
+    ) -> Self {
+        JobEvent {
+            build_id,
+            job_id,
+            kind,
+            state,
+            ref_name,
+            commit,
+            timestamp: Utc::now().timestamp(),
+            request_id,
+        }
+    }
+}
+
+fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_varkey(secret).expect("HMAC can take key of any size");
+    mac.input(body);
+    let bytes = mac.result().code();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Fires `event` at every configured webhook URL with retries, off the
+/// job executor's thread so a slow/unreachable endpoint can't stall it.
+pub fn notify(config: Arc<NotifierConfig>, secret: Vec<u8>, event: JobEvent) {
+    if config.is_empty() {
+        return;
+    }
+    let span = info_span!("webhook_dispatch", build_id = event.build_id, job_id = event.job_id, request_id = event.request_id.as_deref().unwrap_or(""));
+    std::thread::spawn(move || {
+        let _enter = span.enter();
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Failed to serialize webhook payload: {}", e);
+                return;
+            }
+        };
+        let signature = sign(&secret, &body);
+        // Plain blocking client: no actix System runs on this thread to
+        // drive actix_web::client's futures, and its connector panics off-system.
+        let client = reqwest::blocking::Client::new();
+        for url in &config.webhook_urls {
+            dispatch_with_retry(&client, url, &body, &signature);
+        }
+    });
+}
+
+fn dispatch_with_retry(client: &reqwest::blocking::Client, url: &str, body: &[u8], signature: &str) {
+    let mut backoff = Duration::from_millis(INITIAL_BACKOFF_MS);
+    for attempt in 1..=MAX_RETRIES {
+        let result = client
+            .post(url)
+            .header("X-RepoManager-Signature", signature)
+            .header("Content-Type", "application/json")
+            .body(body.to_vec())
+            .send();
+
+        match result {
+            Ok(response) if response.status().is_success() => {
+                return;
+            }
+            Ok(response) => {
+                warn!(
+                    "Webhook {} returned {} (attempt {}/{})",
+                    url,
+                    response.status(),
+                    attempt,
+                    MAX_RETRIES
+                );
+            }
+            Err(e) => {
+                warn!("Webhook {} unreachable (attempt {}/{}): {}", url, attempt, MAX_RETRIES, e);
+            }
+        }
+
+        if attempt < MAX_RETRIES {
+            std::thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+    error!("Giving up on webhook {} after {} attempts", url, MAX_RETRIES);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_is_deterministic_and_key_dependent() {
+        let body = b"{\"job_id\":1}";
+        assert_eq!(sign(b"secret", body), sign(b"secret", body));
+        assert_ne!(sign(b"secret", body), sign(b"other", body));
+    }
+
+    #[test]
+    fn sign_is_lowercase_hex() {
+        let digest = sign(b"secret", b"body");
+        assert_eq!(digest.len(), 64);
+        assert!(digest.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    }
+}
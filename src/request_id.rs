@@ -0,0 +1,66 @@
+use actix_web::http::{HeaderName, HeaderValue};
+use actix_web::middleware::{Middleware, Response, Started};
+use actix_web::{HttpRequest, HttpResponse, Result};
+use std::time::Instant;
+use tracing::Span;
+use uuid::Uuid;
+
+/// Generates a request id for every incoming request, logs start/completion
+/// lines carrying it (replacing `middleware::Logger`, which this replaces),
+/// and echoes it back as `X-Request-Id` so a client can correlate an API
+/// call with the async job it results from. The `Span` stashed alongside it
+/// is there for handlers that want to enter it explicitly (see
+/// `request_id::span`); the middleware itself can't wrap arbitrary handler
+/// futures, so it doesn't correlate logging on its own.
+pub struct RequestId;
+
+struct RequestIdValue {
+    id: String,
+    span: Span,
+    start: Instant,
+}
+
+impl<S> Middleware<S> for RequestId {
+    fn start(&self, req: &HttpRequest<S>) -> Result<Started> {
+        let request_id = Uuid::new_v4().to_string();
+        let span = info_span!("request", request_id = %request_id, path = %req.path());
+        let _entered = span.enter();
+        info!("request started");
+        drop(_entered);
+        req.extensions_mut().insert(RequestIdValue {
+            id: request_id,
+            span,
+            start: Instant::now(),
+        });
+        Ok(Started::Done)
+    }
+
+    fn response(&self, req: &HttpRequest<S>, mut resp: HttpResponse) -> Result<Response> {
+        if let Some(value) = req.extensions().get::<RequestIdValue>() {
+            let _entered = value.span.enter();
+            info!(
+                status = resp.status().as_u16(),
+                duration_ms = value.start.elapsed().as_millis() as u64,
+                "request completed"
+            );
+            if let Ok(header) = HeaderValue::from_str(&value.id) {
+                resp.headers_mut().insert(HeaderName::from_static("x-request-id"), header);
+            }
+        }
+        Ok(Response::Done(resp))
+    }
+}
+
+/// Reads back the request id a previous `RequestId::start` stashed on the
+/// request, for handlers that need to thread it into a spawned job (e.g.
+/// so the webhook notifier payload can carry it).
+pub fn request_id<S>(req: &HttpRequest<S>) -> Option<String> {
+    req.extensions().get::<RequestIdValue>().map(|v| v.id.clone())
+}
+
+/// Reads back the `tracing` span a previous `RequestId::start` created for
+/// the request, so a handler can `.enter()` it around its own work and have
+/// those log lines carry the request id too.
+pub fn span<S>(req: &HttpRequest<S>) -> Option<Span> {
+    req.extensions().get::<RequestIdValue>().map(|v| v.span.clone())
+}
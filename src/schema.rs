@@ -0,0 +1,32 @@
+table! {
+    builds (id) {
+        id -> Int4,
+        created -> Timestamp,
+        repo_state -> Int2,
+        build_repo_base_path -> Text,
+    }
+}
+
+table! {
+    build_refs (id) {
+        id -> Int4,
+        build_id -> Int4,
+        ref_name -> Text,
+        commit -> Text,
+    }
+}
+
+table! {
+    jobs (id) {
+        id -> Int4,
+        kind -> Int2,
+        status -> Int2,
+        build_id -> Nullable<Int4>,
+        contents -> Text,
+        results -> Text,
+        log -> Text,
+        request_id -> Nullable<Text>,
+    }
+}
+
+allow_tables_to_appear_in_same_query!(builds, build_refs, jobs);